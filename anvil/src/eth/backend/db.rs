@@ -7,15 +7,35 @@ use ethers::{
 };
 use foundry_evm::{
     executor::DatabaseRef,
-    revm::{db::CacheDB, Database, DatabaseCommit, InMemoryDB},
+    revm::{
+        db::{AccountState, CacheDB},
+        Database, DatabaseCommit, InMemoryDB,
+    },
 };
+use ethers::utils::hex_literal::hex;
+use linked_hash_map::LinkedHashMap;
+use rlp::RlpStream;
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    io::{Read, Write},
+};
+
+/// keccak256(rlp("")), the hash of the RLP of an empty byte string: the root of an empty
+/// Merkle-Patricia trie.
+const KECCAK_NULL_RLP: H256 =
+    H256(hex!("56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421"));
+
+/// keccak256(""), the code hash of an account with no code.
+const KECCAK_EMPTY: H256 =
+    H256(hex!("c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"));
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct SerializableState {
     accounts: Map<Address, AccountRecord>
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct AccountRecord {
     nonce: u64,
     balance: U256,
@@ -66,6 +86,22 @@ pub trait Db: DatabaseRef + Database + DatabaseCommit + Send + Sync {
     /// Returns `true` if the snapshot was reverted
     fn revert(&mut self, snapshot: U256) -> bool;
 
+    /// Retires a diff baseline previously returned by [Db::snapshot], without reverting any state.
+    ///
+    /// For callers that never intend to revert — e.g. the test harnesses and state-sync tools
+    /// [Db::dump_state_diff] was built for, which take a [Db::snapshot] purely to mark where the
+    /// next diff should start from and periodically roll that baseline forward — calling
+    /// [Db::revert] isn't an option since it now actually rolls back state (see
+    /// [MemBoundedCacheDb]'s impl). Without this, every such checkpoint would stay tracked forever,
+    /// and every subsequent write anywhere in the chain would keep accumulating against it: an
+    /// unbounded memory leak for exactly the long-running-node use case this feature targets.
+    ///
+    /// A typical roll-forward is `let next = db.snapshot(); /* diff against the old one */
+    /// db.close_diff_baseline(previous);`. Returns `true` if `since` was being tracked.
+    fn close_diff_baseline(&mut self, _since: U256) -> bool {
+        false
+    }
+
     /// Returns the state root if possible to compute
     fn maybe_state_root(&self) -> Option<H256> {
         None
@@ -73,6 +109,104 @@ pub trait Db: DatabaseRef + Database + DatabaseCommit + Send + Sync {
 
     /// Returns the current, standalone state of the Db
     fn current_state(&self) -> StateDb;
+
+    /// Returns the parent ("settlement"/L1) chain's state, if one was configured for this
+    /// backend, see [read_remote].
+    fn settlement_layer(&self) -> Option<&StateDb> {
+        None
+    }
+
+    /// Installs (or, with `None`, clears) the parent chain's state that [Db::settlement_layer]
+    /// and the `XCALLOPTIONS` precompile read through.
+    fn set_settlement_layer(&mut self, _layer: Option<StateDb>) {}
+
+    /// Selects which layer subsequent `DatabaseRef`/`Database` reads on this `Db` should target,
+    /// until the next call to [Db::select_layer] or [Db::reset_layer]. This is what the
+    /// `XCALLOPTIONS` precompile calls into, see [read_remote].
+    ///
+    /// Returns [RemoteReadError::NoSettlementLayer], without changing anything, if `layer` is
+    /// [Layer::Settlement] and no [Db::settlement_layer] is configured — callers (the precompile)
+    /// turn that into a revert rather than silently reading local state.
+    fn select_layer(&self, layer: Layer) -> Result<(), RemoteReadError> {
+        match layer {
+            Layer::Local => Ok(()),
+            Layer::Settlement => Err(RemoteReadError::NoSettlementLayer),
+        }
+    }
+
+    /// Resets the active layer back to [Layer::Local]. Must be called once a top-level call frame
+    /// finishes, so a layer selected by one transaction can never leak into the next.
+    fn reset_layer(&self) {}
+
+    /// Returns every account this backend can enumerate, for [Db::dump_state_to] to stream out
+    /// without first materializing the full [Db::dump_state] map.
+    ///
+    /// The default implementation just falls back to [Db::dump_state] — a backend that owns its
+    /// account map in place (like [MemBoundedCacheDb]) should override this to actually stream
+    /// straight from it instead.
+    fn dump_state_iter(&mut self) -> Box<dyn Iterator<Item = (Address, AccountRecord)> + '_> {
+        Box::new(self.dump_state().into_iter())
+    }
+
+    /// Streams all chain data out as a versioned, chunked, compressed snapshot.
+    ///
+    /// Unlike [Db::dump_state], this writes to `writer` rather than returning a map: accounts are
+    /// pulled incrementally from [Db::dump_state_iter] and serialized in fixed-size,
+    /// independently-compressed chunks behind a [SnapshotManifest], so callers can target a file
+    /// or socket directly. Every compressed chunk is buffered in memory until the manifest, which
+    /// carries all chunk hashes, has been written; and with the default [Db::dump_state_iter] the
+    /// full [Db::dump_state] map is materialized first.
+    fn dump_state_to(&mut self, block_number: U256, writer: &mut dyn Write) -> Result<(), SnapshotError> {
+        let state_root = self.maybe_state_root();
+        let accounts = self.dump_state_iter();
+        write_snapshot(writer, accounts, block_number, state_root)
+    }
+
+    /// Streams and verifies a snapshot written by [Db::dump_state_to].
+    ///
+    /// Each chunk's hash is checked against the manifest as it's read, and a manifest whose
+    /// [SnapshotManifest::version] isn't [SNAPSHOT_FORMAT_VERSION] is rejected outright, so a
+    /// snapshot from a newer `anvil` fails loudly instead of silently corrupting this one's state.
+    fn load_state_from(&mut self, reader: &mut dyn Read) -> Result<bool, SnapshotError> {
+        let accounts = read_snapshot(reader)?;
+        Ok(self.load_state(accounts))
+    }
+
+    /// Collapses every transition recorded since the snapshot `since` into the minimal set of
+    /// accounts/slots that actually changed, instead of a full [Db::dump_state] dump.
+    ///
+    /// An included account carries its current nonce/balance/code and *only* the storage slots
+    /// that changed since `since`, not its full storage. A deleted account (e.g. via
+    /// `SELFDESTRUCT`) is represented explicitly as an all-zero [AccountRecord] with no storage,
+    /// distinguishing "now empty" from "untouched" (simply absent from the map).
+    ///
+    /// The default implementation tracks no transitions and always returns an empty diff;
+    /// implementations that want this should keep a [TransitionTracker] and record into it from
+    /// `insert_account`/`set_storage_at`/`commit`, see [TransitionTracker::diff_since].
+    fn dump_state_diff(&self, _since: U256) -> Map<Address, AccountRecord> {
+        Map::new()
+    }
+
+    /// Applies a diff produced by [Db::dump_state_diff]: sets each account's nonce/balance/code
+    /// and the storage slots it carries.
+    fn apply_state_diff(&mut self, diff: Map<Address, AccountRecord>) {
+        for (address, record) in diff {
+            self.insert_account(
+                address,
+                AccountInfo {
+                    nonce: record.nonce,
+                    balance: record.balance,
+                    code_hash: H256::from_slice(&ethers::utils::keccak256(record.code.as_ref()))
+                        .into(),
+                    code: (!record.code.is_empty())
+                        .then(|| foundry_evm::revm::Bytecode::new_raw(record.code.0.clone())),
+                },
+            );
+            for (slot, value) in record.storage {
+                self.set_storage_at(address, slot, value);
+            }
+        }
+    }
 }
 
 /// Convenience impl only used to use any `Db` on the fly as the db layer for revm's CacheDB
@@ -104,9 +238,589 @@ impl<T: DatabaseRef + Send + Sync + Clone> Db for CacheDB<T> {
         false
     }
 
+    fn maybe_state_root(&self) -> Option<H256> {
+        // `CacheDB` caches a `NotExisting` marker (and, in general, truly-empty accounts) on a
+        // lookup miss. Per EIP-161 those are not part of state and must not be hashed in, or the
+        // root won't match what a real client computes for the same state.
+        let accounts = self
+            .accounts
+            .iter()
+            .filter(|(_, account)| {
+                account.account_state != AccountState::NotExisting && !account.info.is_empty()
+            })
+            .map(|(address, account)| (*address, &account.info, &account.storage));
+        Some(trie_hash_db(accounts, |code_hash| self.contracts.get(&code_hash).cloned()))
+    }
+
+    fn current_state(&self) -> StateDb {
+        StateDb::new(InMemoryDB::default())
+    }
+}
+
+/// Computes the root of the secure (`keccak256(address)`-keyed) Merkle-Patricia state trie over
+/// `accounts`, matching the tree an Ethereum client would produce for the `stateRoot` field of a
+/// block header.
+///
+/// `code_by_hash` resolves an account's bytecode from its `code_hash` when it isn't already
+/// attached to the [AccountInfo] (as is the case for [revm::db::CacheDB]'s `DbAccount`).
+///
+/// This does not cache per-account storage trie roots across calls; backends that own their full
+/// account map and call this repeatedly should keep their own [StateRootCache] and only recompute
+/// the storage trie for accounts whose storage actually changed since the last call, see
+/// [StateRootCache::root].
+fn trie_hash_db<'a>(
+    accounts: impl Iterator<Item = (Address, &'a AccountInfo, &'a HashMap<U256, U256>)>,
+    code_by_hash: impl Fn(H256) -> Option<bytes::Bytes>,
+) -> H256 {
+    let entries = accounts.map(|(address, info, storage)| {
+        let storage_root = storage_trie_root(storage);
+        let code_hash = account_code_hash(info, &code_by_hash);
+        let value = trie_account_rlp(info.nonce, info.balance, storage_root, code_hash);
+        (ethers::utils::keccak256(address.as_bytes()), value)
+    });
+    H256::from_slice(triehash::trie_root::<keccak_hasher::KeccakHasher, _, _, _>(entries).as_bytes())
+}
+
+/// Resolves an account's code hash.
+///
+/// `info.code` is hashed fresh via `Bytecode::original_bytes()` (never `Bytecode::bytes()`, since
+/// the latter is revm's internal, analysis-padded buffer and hashing it would produce a code hash
+/// that doesn't match the real, unpadded bytecode) whenever it's attached, and takes priority over
+/// `info.code_hash`. This matters because [Db::set_code] only ever assigns `info.code`, not
+/// `info.code_hash` — so an account whose code was set that way (e.g. `anvil_setCode`/`vm.etch`)
+/// would otherwise keep its stale, pre-existing `code_hash` forever. Only when there's no attached
+/// code is `info.code_hash` trusted as-is, falling back to a `code_by_hash` lookup (and finally
+/// [KECCAK_EMPTY]) when it's unset.
+fn account_code_hash(info: &AccountInfo, code_by_hash: &impl Fn(H256) -> Option<bytes::Bytes>) -> H256 {
+    if let Some(code) = info.code.as_ref() {
+        return H256::from_slice(&ethers::utils::keccak256(code.original_bytes()))
+    }
+    let code_hash: H256 = info.code_hash.into();
+    if code_hash != H256::zero() {
+        return code_hash
+    }
+    match code_by_hash(code_hash) {
+        Some(code) if !code.is_empty() => {
+            H256::from_slice(&ethers::utils::keccak256(code))
+        }
+        _ => KECCAK_EMPTY,
+    }
+}
+
+/// Computes the root of an account's per-slot storage trie, keyed by `keccak256(slot)` with each
+/// leaf holding the RLP of the big-endian, zero-trimmed slot value. Empty storage yields
+/// [KECCAK_NULL_RLP], matching an Ethereum client's `storageRoot` for an account with no storage.
+fn storage_trie_root(storage: &HashMap<U256, U256>) -> H256 {
+    if storage.is_empty() {
+        return KECCAK_NULL_RLP
+    }
+    let entries = storage.iter().filter(|(_, value)| !value.is_zero()).map(|(slot, value)| {
+        let mut slot_bytes = [0u8; 32];
+        slot.to_big_endian(&mut slot_bytes);
+        let mut value_bytes = [0u8; 32];
+        value.to_big_endian(&mut value_bytes);
+        let trimmed = &value_bytes[value_bytes.iter().position(|b| *b != 0).unwrap_or(31)..];
+        (ethers::utils::keccak256(slot_bytes), rlp::encode(&trimmed).to_vec())
+    });
+    H256::from_slice(triehash::trie_root::<keccak_hasher::KeccakHasher, _, _, _>(entries).as_bytes())
+}
+
+/// RLP-encodes an account's trie leaf value: `[nonce, balance, storageRoot, codeHash]`.
+fn trie_account_rlp(nonce: u64, balance: U256, storage_root: H256, code_hash: H256) -> Vec<u8> {
+    let mut stream = RlpStream::new_list(4);
+    stream.append(&nonce);
+    stream.append(&balance);
+    stream.append(&storage_root.as_bytes());
+    stream.append(&code_hash.as_bytes());
+    stream.out().to_vec()
+}
+
+/// Caches per-account storage trie roots between successive [Db::maybe_state_root] calls on a
+/// backend that owns its full account map, so only accounts whose storage changed since the last
+/// call need their storage trie rebuilt.
+///
+/// Backends that can enumerate their accounts and track dirty storage (unlike the generic
+/// [CacheDB] blanket impl above, which has nowhere to keep this across calls) should hold one of
+/// these and mark an address dirty via [StateRootCache::storage_changed] on every
+/// `set_storage_at`/`commit`.
+#[derive(Debug, Default)]
+pub struct StateRootCache {
+    storage_roots: RefCell<HashMap<Address, H256>>,
+    dirty: RefCell<HashSet<Address>>,
+}
+
+// === impl StateRootCache ===
+
+impl StateRootCache {
+    /// Marks `address`'s storage as changed, forcing its storage trie root to be rebuilt on the
+    /// next [StateRootCache::root] call.
+    pub fn storage_changed(&self, address: Address) {
+        self.dirty.borrow_mut().remove(&address);
+        self.storage_roots.borrow_mut().remove(&address);
+        self.dirty.borrow_mut().insert(address);
+    }
+
+    /// Computes the state root over `accounts`, reusing cached storage trie roots for any account
+    /// not marked dirty since the last call.
+    pub fn root<'a>(
+        &self,
+        accounts: impl Iterator<Item = (Address, &'a AccountInfo, &'a HashMap<U256, U256>)>,
+        code_by_hash: impl Fn(H256) -> Option<bytes::Bytes>,
+    ) -> H256 {
+        let mut dirty = self.dirty.borrow_mut();
+        let mut roots = self.storage_roots.borrow_mut();
+        let entries: Vec<_> = accounts
+            .map(|(address, info, storage)| {
+                let storage_root = if dirty.remove(&address) || !roots.contains_key(&address) {
+                    let root = storage_trie_root(storage);
+                    roots.insert(address, root);
+                    root
+                } else {
+                    roots[&address]
+                };
+                let code_hash = account_code_hash(info, &code_by_hash);
+                let value = trie_account_rlp(info.nonce, info.balance, storage_root, code_hash);
+                (ethers::utils::keccak256(address.as_bytes()), value)
+            })
+            .collect();
+        H256::from_slice(
+            triehash::trie_root::<keccak_hasher::KeccakHasher, _, _, _>(entries).as_bytes(),
+        )
+    }
+}
+
+/// Default number of [AccountInfo] entries kept in a [MemBoundedCacheDb]'s account cache.
+pub const DEFAULT_ACCOUNT_CACHE_CAPACITY: usize = 50_000;
+
+/// Default number of storage slots kept in a [MemBoundedCacheDb]'s storage cache.
+pub const DEFAULT_STORAGE_CACHE_CAPACITY: usize = 500_000;
+
+/// A [Db] implementation that wraps another [DatabaseRef] (typically a fork's remote db) with two
+/// independent, memory-bounded LRU caches: one for account info, one for storage slots.
+///
+/// Unlike [CacheDB], entries here are evicted once their respective cache exceeds its configured
+/// capacity. This keeps long-running forked sessions from growing without bound. Values that were
+/// only ever read from the remote are safe to evict, because they can always be re-fetched. Any
+/// entry that was locally mutated (`insert_account`, `set_storage_at`, etc.) is pinned and will
+/// never be evicted until it's explicitly removed, since it has no source of truth to refetch it
+/// from.
+pub struct MemBoundedCacheDb<T> {
+    /// The underlying database entries are fetched from on a cache miss
+    db: T,
+    /// LRU cache of accounts, most-recently-used at the back
+    accounts: LinkedHashMap<Address, AccountInfo>,
+    /// LRU cache of storage slots, most-recently-used at the back
+    storage: LinkedHashMap<(Address, U256), U256>,
+    /// Accounts that were locally mutated and must never be evicted
+    dirty_accounts: HashSet<Address>,
+    /// Storage slots that were locally mutated and must never be evicted
+    dirty_storage: HashSet<(Address, U256)>,
+    /// Maximum number of accounts to keep cached
+    account_capacity: usize,
+    /// Maximum number of storage slots to keep cached
+    storage_capacity: usize,
+    /// The parent ("settlement"/L1) chain's state, if one was configured, see
+    /// [Db::settlement_layer] and [xcalloptions_precompile].
+    settlement: Option<StateDb>,
+    /// Which layer (local or settlement) `basic`/`storage` reads currently target, selected via
+    /// [Db::select_layer] (what [xcalloptions_precompile] calls into).
+    active_layer: ActiveLayer,
+    /// Per-snapshot account/storage transitions, backing [Db::dump_state_diff].
+    transitions: TransitionTracker,
+    /// Next id [MemBoundedCacheDb::snapshot] will hand out.
+    next_snapshot_id: U256,
+    /// Caches per-account storage trie roots between [Db::maybe_state_root] calls, see
+    /// [StateRootCache].
+    state_root_cache: StateRootCache,
+    /// Set once [MemBoundedCacheDb::evict_accounts]/[MemBoundedCacheDb::evict_storage] has ever
+    /// actually evicted a clean entry. A `stateRoot` must be a pure function of chain state, not
+    /// of LRU eviction history — once this is `true`, [Db::maybe_state_root] can no longer see the
+    /// whole live account set and must refuse rather than silently compute a wrong root, see
+    /// [MemBoundedCacheDb]'s `maybe_state_root` impl.
+    evicted_clean_entry: bool,
+}
+
+// === impl MemBoundedCacheDb ===
+
+impl<T> MemBoundedCacheDb<T> {
+    /// Creates a new, empty cache wrapping `db`, with the given capacities for the account and
+    /// storage caches respectively.
+    pub fn new(db: T, account_capacity: usize, storage_capacity: usize) -> Self {
+        Self {
+            db,
+            accounts: LinkedHashMap::new(),
+            storage: LinkedHashMap::new(),
+            dirty_accounts: HashSet::new(),
+            dirty_storage: HashSet::new(),
+            account_capacity,
+            storage_capacity,
+            settlement: None,
+            active_layer: ActiveLayer::default(),
+            transitions: TransitionTracker::default(),
+            next_snapshot_id: U256::zero(),
+            state_root_cache: StateRootCache::default(),
+            evicted_clean_entry: false,
+        }
+    }
+
+    /// Creates a new cache using [DEFAULT_ACCOUNT_CACHE_CAPACITY] and
+    /// [DEFAULT_STORAGE_CACHE_CAPACITY].
+    pub fn with_default_capacity(db: T) -> Self {
+        Self::new(db, DEFAULT_ACCOUNT_CACHE_CAPACITY, DEFAULT_STORAGE_CACHE_CAPACITY)
+    }
+
+    /// Like [MemBoundedCacheDb::new], but also installs `settlement` as the parent chain's state
+    /// up front, so [Db::settlement_layer] and the `XCALLOPTIONS` precompile (see
+    /// [xcalloptions_precompile]) can read through it from the very first call.
+    pub fn with_settlement_layer(
+        db: T,
+        account_capacity: usize,
+        storage_capacity: usize,
+        settlement: StateDb,
+    ) -> Self {
+        let mut this = Self::new(db, account_capacity, storage_capacity);
+        this.settlement = Some(settlement);
+        this
+    }
+
+    /// Moves the entry for `address` to the back of the account LRU, marking it most-recently-used
+    fn touch_account(&mut self, address: Address) {
+        self.accounts.get_refresh(&address);
+    }
+
+    /// Moves the entry for `key` to the back of the storage LRU, marking it most-recently-used
+    fn touch_storage(&mut self, key: (Address, U256)) {
+        self.storage.get_refresh(&key);
+    }
+
+    /// Inserts `info` for `address`, evicting the least-recently-used clean account if the cache
+    /// is over capacity.
+    fn cache_account(&mut self, address: Address, info: AccountInfo) {
+        self.accounts.insert(address, info);
+        self.evict_accounts();
+    }
+
+    /// Inserts `val` for `(address, slot)`, evicting the least-recently-used clean slot if the
+    /// cache is over capacity.
+    fn cache_storage(&mut self, address: Address, slot: U256, val: U256) {
+        self.storage.insert((address, slot), val);
+        self.evict_storage();
+    }
+
+    fn evict_accounts(&mut self) {
+        while self.accounts.len() > self.account_capacity {
+            let Some(lru) = self.accounts.keys().find(|a| !self.dirty_accounts.contains(*a)).copied() else {
+                // every cached account is dirty, nothing left that's safe to evict
+                break
+            };
+            self.accounts.remove(&lru);
+            self.evicted_clean_entry = true;
+        }
+    }
+
+    fn evict_storage(&mut self) {
+        while self.storage.len() > self.storage_capacity {
+            let Some(lru) =
+                self.storage.keys().find(|key| !self.dirty_storage.contains(*key)).copied()
+            else {
+                // every cached slot is dirty, nothing left that's safe to evict
+                break
+            };
+            self.storage.remove(&lru);
+            self.evicted_clean_entry = true;
+        }
+    }
+}
+
+impl<T: DatabaseRef> DatabaseRef for MemBoundedCacheDb<T> {
+    fn basic(&self, address: H160) -> AccountInfo {
+        if self.active_layer.get() == Layer::Settlement {
+            // settlement layer was never evicted/cached locally, it's read straight through
+            return self.settlement.as_ref().map(|s| s.basic(address)).unwrap_or_default()
+        }
+        if let Some(info) = self.accounts.get(&address) {
+            return info.clone()
+        }
+        self.db.basic(address)
+    }
+
+    fn code_by_hash(&self, code_hash: H256) -> bytes::Bytes {
+        if self.active_layer.get() == Layer::Settlement {
+            return self
+                .settlement
+                .as_ref()
+                .map(|s| s.code_by_hash(code_hash))
+                .unwrap_or_default()
+        }
+        self.db.code_by_hash(code_hash)
+    }
+
+    fn storage(&self, address: H160, index: U256) -> U256 {
+        if self.active_layer.get() == Layer::Settlement {
+            return self
+                .settlement
+                .as_ref()
+                .map(|s| s.storage(address, index))
+                .unwrap_or_default()
+        }
+        if let Some(val) = self.storage.get(&(address, index)) {
+            return *val
+        }
+        self.db.storage(address, index)
+    }
+
+    fn block_hash(&self, number: U256) -> H256 {
+        self.db.block_hash(number)
+    }
+}
+
+impl<T: DatabaseRef + Send + Sync + Clone> Db for MemBoundedCacheDb<T> {
+    fn insert_account(&mut self, address: Address, account: AccountInfo) {
+        let original = self.accounts.get(&address).cloned();
+        self.transitions.record_account_change(address, original, Some(account.clone()));
+        self.dirty_accounts.insert(address);
+        self.cache_account(address, account);
+    }
+
+    fn set_storage_at(&mut self, address: Address, slot: U256, val: U256) {
+        let original = self.storage.get(&(address, slot)).copied().unwrap_or_default();
+        let info = self.accounts.get(&address).cloned().unwrap_or_default();
+        self.transitions.record_storage_change(address, info, slot, original, val);
+        self.dirty_storage.insert((address, slot));
+        self.cache_storage(address, slot, val);
+        self.state_root_cache.storage_changed(address);
+    }
+
+    fn dump_state(&mut self) -> Map<Address, AccountRecord> {
+        self.dump_state_iter().collect()
+    }
+
+    fn dump_state_iter(&mut self) -> Box<dyn Iterator<Item = (Address, AccountRecord)> + '_> {
+        // `MemBoundedCacheDb` never holds the full account set in memory — a clean (never
+        // locally mutated) entry is only a cache of data that's always refetchable from `self.db`
+        // on the next miss, so the one thing a dump of this backend actually needs to preserve is
+        // every account that *was* locally mutated, i.e. every address `dirty_accounts` or
+        // `dirty_storage` pins. Reads straight from `self.accounts`/`self.storage` one address at
+        // a time, rather than collecting a full `Map` first, so [Db::dump_state_to] can stream it
+        // out a chunk at a time.
+        let accounts = &self.accounts;
+        let storage = &self.storage;
+        let db = &self.db;
+        let addresses: HashSet<Address> = self
+            .dirty_accounts
+            .iter()
+            .copied()
+            .chain(self.dirty_storage.iter().map(|(address, _)| *address))
+            .collect();
+        Box::new(addresses.into_iter().map(move |address| {
+            let info = accounts.get(&address).cloned().unwrap_or_else(|| db.basic(address));
+            let account_storage = storage
+                .iter()
+                .filter(|((a, _), _)| *a == address)
+                .map(|((_, slot), value)| (*slot, *value))
+                .collect();
+            let record = AccountRecord {
+                nonce: info.nonce,
+                balance: info.balance,
+                code: info
+                    .code
+                    .as_ref()
+                    .map(|c| c.original_bytes().to_vec().into())
+                    .unwrap_or_default(),
+                storage: account_storage,
+            };
+            (address, record)
+        }))
+    }
+
+    fn load_state(&mut self, buf: Map<Address, AccountRecord>) -> bool {
+        self.apply_state_diff(buf);
+        true
+    }
+
+    fn snapshot(&mut self) -> U256 {
+        let id = self.next_snapshot_id;
+        self.next_snapshot_id += U256::one();
+        self.transitions.open_snapshot(id);
+        id
+    }
+
+    fn revert(&mut self, snapshot: U256) -> bool {
+        // Borrow the transitions out from under `self.transitions` first: replaying them touches
+        // `self.accounts`/`self.storage`, which `self.transitions` doesn't own.
+        let Some(changes) = self.transitions.transitions_since(snapshot) else { return false };
+        let changes = changes.clone();
+        for (address, transition) in &changes {
+            match &transition.original_info {
+                Some(info) => {
+                    self.accounts.insert(*address, info.clone());
+                }
+                None => {
+                    // the account didn't exist before the snapshot was taken
+                    self.accounts.remove(address);
+                    self.dirty_accounts.remove(address);
+                }
+            }
+            for (&slot, &(original, _)) in &transition.storage {
+                self.storage.insert((*address, slot), original);
+            }
+            self.state_root_cache.storage_changed(*address);
+        }
+        self.transitions.close_snapshot(snapshot)
+    }
+
+    fn close_diff_baseline(&mut self, since: U256) -> bool {
+        self.transitions.close_snapshot(since)
+    }
+
+    fn dump_state_diff(&self, since: U256) -> Map<Address, AccountRecord> {
+        self.transitions.diff_since(since)
+    }
+
     fn current_state(&self) -> StateDb {
         StateDb::new(InMemoryDB::default())
     }
+
+    fn maybe_state_root(&self) -> Option<H256> {
+        // Like [CacheDB]'s impl, this only ever hashes in the accounts this backend currently has
+        // cached, not the fork's entire account set — true per EIP-161: an account must be dropped
+        // (rather than just absent from the cache) if it's empty.
+        //
+        // That's only sound while the cache has never evicted a clean entry: once eviction has
+        // dropped a still-live account/slot (`evicted_clean_entry`), `self.accounts`/`self.storage`
+        // is no longer the whole state, and a root computed over it would be a function of LRU
+        // eviction history rather than of actual chain state. Refuse rather than silently return a
+        // `stateRoot` a real client wouldn't agree with.
+        if self.evicted_clean_entry {
+            return None
+        }
+        let mut storage_by_account: HashMap<Address, HashMap<U256, U256>> = HashMap::new();
+        for (&(address, slot), &value) in self.storage.iter() {
+            storage_by_account.entry(address).or_default().insert(slot, value);
+        }
+        let empty_storage = HashMap::new();
+        let accounts = self.accounts.iter().filter(|(_, info)| !info.is_empty()).map(
+            |(address, info)| {
+                let storage = storage_by_account.get(address).unwrap_or(&empty_storage);
+                (*address, info, storage)
+            },
+        );
+        Some(self.state_root_cache.root(accounts, |code_hash| {
+            let code = self.db.code_by_hash(code_hash);
+            (!code.is_empty()).then_some(code)
+        }))
+    }
+
+    fn settlement_layer(&self) -> Option<&StateDb> {
+        self.settlement.as_ref()
+    }
+
+    fn set_settlement_layer(&mut self, layer: Option<StateDb>) {
+        self.settlement = layer;
+    }
+
+    fn select_layer(&self, layer: Layer) -> Result<(), RemoteReadError> {
+        if layer == Layer::Settlement && self.settlement.is_none() {
+            return Err(RemoteReadError::NoSettlementLayer)
+        }
+        self.active_layer.select(layer);
+        Ok(())
+    }
+
+    fn reset_layer(&self) {
+        self.active_layer.reset();
+    }
+}
+
+impl<T> Database for MemBoundedCacheDb<T>
+where
+    T: DatabaseRef + Send + Sync + Clone,
+{
+    fn basic(&mut self, address: H160) -> AccountInfo {
+        if self.active_layer.get() == Layer::Settlement {
+            return DatabaseRef::basic(self, address)
+        }
+        if let Some(info) = self.accounts.get(&address).cloned() {
+            self.touch_account(address);
+            return info
+        }
+        let info = self.db.basic(address);
+        self.cache_account(address, info.clone());
+        info
+    }
+
+    fn code_by_hash(&mut self, code_hash: H256) -> bytes::Bytes {
+        if self.active_layer.get() == Layer::Settlement {
+            return DatabaseRef::code_by_hash(self, code_hash)
+        }
+        self.db.code_by_hash(code_hash)
+    }
+
+    fn storage(&mut self, address: H160, index: U256) -> U256 {
+        if self.active_layer.get() == Layer::Settlement {
+            return DatabaseRef::storage(self, address, index)
+        }
+        let key = (address, index);
+        if let Some(val) = self.storage.get(&key).copied() {
+            self.touch_storage(key);
+            return val
+        }
+        let val = self.db.storage(address, index);
+        self.cache_storage(address, index, val);
+        val
+    }
+
+    fn block_hash(&mut self, number: U256) -> H256 {
+        self.db.block_hash(number)
+    }
+}
+
+impl<T> DatabaseCommit for MemBoundedCacheDb<T>
+where
+    T: DatabaseRef + Send + Sync + Clone,
+{
+    fn commit(&mut self, changes: HashMap<Address, foundry_evm::revm::Account>) {
+        for (address, account) in changes {
+            let original = self.accounts.get(&address).cloned();
+            self.dirty_accounts.insert(address);
+            if account.is_destroyed {
+                // SELFDESTRUCT wipes the account's storage entirely, including slots this tx
+                // never touched — drop every cached slot for `address`, not just the ones
+                // `account.storage` carries, or a later read of an untouched slot would still
+                // return its stale pre-destruction value instead of 0.
+                let stale: Vec<(Address, U256)> =
+                    self.storage.keys().filter(|(a, _)| *a == address).copied().collect();
+                for key in stale {
+                    self.storage.remove(&key);
+                    self.dirty_storage.remove(&key);
+                }
+            }
+            for (slot, value) in &account.storage {
+                self.dirty_storage.insert((address, *slot));
+                self.transitions.record_storage_change(
+                    address,
+                    account.info.clone(),
+                    *slot,
+                    value.previous_or_original_value,
+                    value.present_value(),
+                );
+                self.storage.insert((address, *slot), value.present_value());
+            }
+            self.state_root_cache.storage_changed(address);
+            if account.is_destroyed {
+                self.transitions.record_account_change(address, original, None);
+            } else {
+                self.transitions.record_account_change(address, original, Some(account.info.clone()));
+            }
+            self.accounts.insert(address, account.info);
+        }
+        self.evict_accounts();
+        self.evict_storage();
+        // a commit happens exactly once, after a transaction's top-level call frame finishes
+        // executing, so this is where a layer selected by `XCALLOPTIONS` gets reset before the
+        // next transaction can run
+        self.active_layer.reset();
+    }
 }
 
 /// Represents a state at certain point
@@ -137,3 +851,739 @@ impl DatabaseRef for StateDb {
         self.0.block_hash(number)
     }
 }
+
+/// A read-through cache over an arbitrary [DatabaseRef] that never writes back to the underlying
+/// store.
+///
+/// Building a speculative block (or running `eth_call`/gas estimation) touches the same handful
+/// of accounts over and over, but must never leave a trace in the real, committed `Db`. Wrapping
+/// the real db with [CachedReads::as_db] gives a [Database] + [DatabaseCommit] view where reads
+/// are memoized here and `commit`s land only in this cache.
+/// [Backend::pending_block()](crate::eth::backend::mem::Backend::pending_block) does not use it;
+/// it builds on [CacheDB] through the blanket [Db] impl above.
+#[derive(Clone, Debug, Default)]
+pub struct CachedReads {
+    /// Account info and storage, keyed by address
+    accounts: RefCell<HashMap<Address, (AccountInfo, HashMap<U256, U256>)>>,
+    /// Contract bytecode, keyed by code hash
+    code: RefCell<HashMap<H256, bytes::Bytes>>,
+    /// Historical block hashes, keyed by block number
+    block_hashes: RefCell<HashMap<U256, H256>>,
+}
+
+// === impl CachedReads ===
+
+impl CachedReads {
+    /// Wraps `db` in a view that reads through this cache, memoizing misses, and never commits
+    /// back to `db` itself.
+    pub fn as_db<T: DatabaseRef>(&mut self, db: T) -> CachedReadsDbMut<'_, T> {
+        CachedReadsDbMut { cached_reads: self, db }
+    }
+}
+
+/// A [Database] + [DatabaseCommit] view over a [CachedReads] cache and an underlying
+/// [DatabaseRef], returned by [CachedReads::as_db].
+///
+/// All reads are served from (and memoized into) the cache; all commits are applied only to the
+/// cache, never to `db`.
+pub struct CachedReadsDbMut<'a, T> {
+    cached_reads: &'a mut CachedReads,
+    db: T,
+}
+
+impl<'a, T: DatabaseRef> DatabaseRef for CachedReadsDbMut<'a, T> {
+    fn basic(&self, address: H160) -> AccountInfo {
+        if let Some((info, _)) = self.cached_reads.accounts.borrow().get(&address) {
+            return info.clone()
+        }
+        let info = self.db.basic(address);
+        self.cached_reads.accounts.borrow_mut().insert(address, (info.clone(), HashMap::new()));
+        info
+    }
+
+    fn code_by_hash(&self, code_hash: H256) -> bytes::Bytes {
+        if let Some(code) = self.cached_reads.code.borrow().get(&code_hash) {
+            return code.clone()
+        }
+        let code = self.db.code_by_hash(code_hash);
+        self.cached_reads.code.borrow_mut().insert(code_hash, code.clone());
+        code
+    }
+
+    fn storage(&self, address: H160, index: U256) -> U256 {
+        if let Some(value) =
+            self.cached_reads.accounts.borrow().get(&address).and_then(|(_, s)| s.get(&index))
+        {
+            return *value
+        }
+        let value = self.db.storage(address, index);
+        self.cached_reads
+            .accounts
+            .borrow_mut()
+            .entry(address)
+            .or_insert_with(|| (self.db.basic(address), HashMap::new()))
+            .1
+            .insert(index, value);
+        value
+    }
+
+    fn block_hash(&self, number: U256) -> H256 {
+        if let Some(hash) = self.cached_reads.block_hashes.borrow().get(&number) {
+            return *hash
+        }
+        let hash = self.db.block_hash(number);
+        self.cached_reads.block_hashes.borrow_mut().insert(number, hash);
+        hash
+    }
+}
+
+impl<'a, T: DatabaseRef> Database for CachedReadsDbMut<'a, T> {
+    fn basic(&mut self, address: H160) -> AccountInfo {
+        DatabaseRef::basic(self, address)
+    }
+
+    fn code_by_hash(&mut self, code_hash: H256) -> bytes::Bytes {
+        DatabaseRef::code_by_hash(self, code_hash)
+    }
+
+    fn storage(&mut self, address: H160, index: U256) -> U256 {
+        DatabaseRef::storage(self, address, index)
+    }
+
+    fn block_hash(&mut self, number: U256) -> H256 {
+        DatabaseRef::block_hash(self, number)
+    }
+}
+
+impl<'a, T: DatabaseRef> DatabaseCommit for CachedReadsDbMut<'a, T> {
+    fn commit(&mut self, changes: HashMap<Address, foundry_evm::revm::Account>) {
+        let mut accounts = self.cached_reads.accounts.borrow_mut();
+        for (address, account) in changes {
+            let entry = accounts.entry(address).or_insert_with(|| (account.info.clone(), HashMap::new()));
+            entry.0 = account.info.clone();
+            if account.is_destroyed {
+                // SELFDESTRUCT wipes the account's storage entirely; drop whatever this cache
+                // memoized for it before this tx's own writes (if any) are applied below, or a
+                // later read of an untouched slot would still return its stale cached value.
+                entry.1.clear();
+            }
+            for (slot, value) in account.storage {
+                entry.1.insert(slot, value.present_value());
+            }
+        }
+    }
+}
+
+/// On-wire format version for [Db::dump_state_to]/[Db::load_state_from] snapshots.
+///
+/// Bump this whenever the manifest or chunk layout changes in a way an older loader can't
+/// interpret; [read_snapshot] refuses to load a manifest carrying a version other than this one.
+pub const SNAPSHOT_FORMAT_VERSION: u32 = 1;
+
+/// Number of accounts serialized into a single snapshot chunk before it's compressed.
+pub const SNAPSHOT_CHUNK_SIZE: usize = 1_000;
+
+/// Errors produced while writing or reading a [dump_state_to](Db::dump_state_to) snapshot.
+#[derive(thiserror::Error, Debug)]
+pub enum SnapshotError {
+    #[error("unsupported snapshot format version {0}, expected {SNAPSHOT_FORMAT_VERSION}")]
+    UnsupportedVersion(u32),
+    #[error("snapshot chunk {index} failed its hash check, snapshot is corrupt")]
+    ChunkHashMismatch { index: usize },
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("failed to (de)serialize snapshot data: {0}")]
+    Codec(#[from] bincode::Error),
+    #[error("failed to (de)compress snapshot chunk: {0}")]
+    Compression(#[from] snap::Error),
+}
+
+/// Header preceding a streamed snapshot's compressed chunks: everything a reader needs to know
+/// how many chunks to expect, whether it understands the format, and whether each chunk arrived
+/// intact.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SnapshotManifest {
+    /// Format version this snapshot was written with, see [SNAPSHOT_FORMAT_VERSION]
+    version: u32,
+    /// Block number the snapshot was taken at
+    block_number: U256,
+    /// State root at the time the snapshot was taken, if the [Db] could compute one
+    state_root: Option<H256>,
+    /// keccak256 of each chunk's compressed bytes, in the order the chunks are written
+    chunk_hashes: Vec<H256>,
+}
+
+/// Writes `accounts` to `writer` as a [SnapshotManifest] followed by its compressed chunks.
+fn write_snapshot(
+    writer: &mut dyn Write,
+    accounts: impl Iterator<Item = (Address, AccountRecord)>,
+    block_number: U256,
+    state_root: Option<H256>,
+) -> Result<(), SnapshotError> {
+    // Pulled and compressed one [SNAPSHOT_CHUNK_SIZE] batch at a time, rather than collecting
+    // `accounts` into a single in-memory `Vec`/`Map` first — that's the whole point of a streaming
+    // snapshot for a multi-gigabyte forked state. The manifest still needs every chunk's hash
+    // before it can be written, so the compressed chunks themselves (much smaller than the
+    // decoded account data) are buffered until then.
+    let mut compressed_chunks: Vec<Vec<u8>> = Vec::new();
+    let mut batch: Vec<(Address, AccountRecord)> = Vec::with_capacity(SNAPSHOT_CHUNK_SIZE);
+    for entry in accounts {
+        batch.push(entry);
+        if batch.len() == SNAPSHOT_CHUNK_SIZE {
+            let raw = bincode::serialize(&batch)?;
+            compressed_chunks.push(snap::raw::Encoder::new().compress_vec(&raw)?);
+            batch.clear();
+        }
+    }
+    if !batch.is_empty() {
+        let raw = bincode::serialize(&batch)?;
+        compressed_chunks.push(snap::raw::Encoder::new().compress_vec(&raw)?);
+    }
+
+    let chunk_hashes = compressed_chunks
+        .iter()
+        .map(|chunk| H256::from_slice(&ethers::utils::keccak256(chunk)))
+        .collect();
+
+    let manifest = SnapshotManifest {
+        version: SNAPSHOT_FORMAT_VERSION,
+        block_number,
+        state_root,
+        chunk_hashes,
+    };
+    let manifest_bytes = bincode::serialize(&manifest)?;
+    writer.write_all(&(manifest_bytes.len() as u64).to_le_bytes())?;
+    writer.write_all(&manifest_bytes)?;
+
+    for chunk in compressed_chunks {
+        writer.write_all(&(chunk.len() as u64).to_le_bytes())?;
+        writer.write_all(&chunk)?;
+    }
+    Ok(())
+}
+
+/// Reads and verifies a snapshot written by [write_snapshot], returning its accounts.
+///
+/// Rejects manifests whose [SnapshotManifest::version] isn't [SNAPSHOT_FORMAT_VERSION], and
+/// checks every chunk's hash against the manifest as it's decompressed.
+fn read_snapshot(reader: &mut dyn Read) -> Result<Map<Address, AccountRecord>, SnapshotError> {
+    let manifest_len = read_u64(reader)? as usize;
+    let mut manifest_bytes = vec![0u8; manifest_len];
+    reader.read_exact(&mut manifest_bytes)?;
+    let manifest: SnapshotManifest = bincode::deserialize(&manifest_bytes)?;
+
+    if manifest.version != SNAPSHOT_FORMAT_VERSION {
+        return Err(SnapshotError::UnsupportedVersion(manifest.version))
+    }
+
+    let mut accounts = Map::new();
+    for (index, expected_hash) in manifest.chunk_hashes.iter().enumerate() {
+        let chunk_len = read_u64(reader)? as usize;
+        let mut compressed = vec![0u8; chunk_len];
+        reader.read_exact(&mut compressed)?;
+
+        let actual_hash = H256::from_slice(&ethers::utils::keccak256(&compressed));
+        if &actual_hash != expected_hash {
+            return Err(SnapshotError::ChunkHashMismatch { index })
+        }
+
+        let raw = snap::raw::Decoder::new().decompress_vec(&compressed)?;
+        let entries: Vec<(Address, AccountRecord)> = bincode::deserialize(&raw)?;
+        accounts.extend(entries);
+    }
+    Ok(accounts)
+}
+
+fn read_u64(reader: &mut dyn Read) -> std::io::Result<u64> {
+    let mut buf = [0u8; 8];
+    reader.read_exact(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+/// A single account's accumulated changes since a snapshot generation was taken, the way revm's
+/// bundle/transition state tracks them.
+#[derive(Clone, Debug, Default)]
+struct AccountTransition {
+    /// Account info as of just before the snapshot, `None` if the account didn't exist yet
+    original_info: Option<AccountInfo>,
+    /// Most recent account info. Only meaningful when `deleted` is `false` — a storage-only write
+    /// still populates this with the account's current info so [TransitionTracker::diff_since] has
+    /// a nonce/balance/code to report alongside the changed slots.
+    new_info: Option<AccountInfo>,
+    /// Whether the account has been deleted (e.g. via `SELFDESTRUCT`) since the snapshot.
+    /// Tracked explicitly rather than inferred from `new_info.is_none()`, since a storage-only
+    /// write never touches `new_info` in the first place and must not be mistaken for a deletion.
+    deleted: bool,
+    /// `slot -> (original, most recent)` value, first touch fixes `original`
+    storage: HashMap<U256, (U256, U256)>,
+}
+
+/// Tracks per-account and per-slot transitions against every currently open [Db::snapshot]
+/// generation, so a [Db] can answer [Db::dump_state_diff]-style queries without re-diffing full
+/// state dumps.
+///
+/// A concrete backend that wants real `dump_state_diff` support holds one of these, calls
+/// [TransitionTracker::open_snapshot]/[TransitionTracker::close_snapshot] from its
+/// `snapshot`/`revert` implementation, and forwards every account/storage write into
+/// [TransitionTracker::record_account_change]/[TransitionTracker::record_storage_change].
+#[derive(Debug, Default)]
+pub struct TransitionTracker {
+    /// Open snapshot generation -> accumulated per-address transitions since it was taken
+    transitions: HashMap<U256, HashMap<Address, AccountTransition>>,
+}
+
+// === impl TransitionTracker ===
+
+impl TransitionTracker {
+    /// Starts tracking transitions against a newly created snapshot generation `id`.
+    pub fn open_snapshot(&mut self, id: U256) {
+        self.transitions.entry(id).or_default();
+    }
+
+    /// Stops tracking transitions for snapshot generation `id`, e.g. once it's been reverted or
+    /// is no longer reachable. Returns whether `id` was actually being tracked.
+    pub fn close_snapshot(&mut self, id: U256) -> bool {
+        self.transitions.remove(&id).is_some()
+    }
+
+    /// Returns every address that changed since snapshot generation `id` was taken, along with its
+    /// recorded transition, so a [Db::revert] implementation can replay `original_info`/`storage`
+    /// back onto its own account/storage maps. `None` if `id` isn't currently tracked.
+    fn transitions_since(&self, id: U256) -> Option<&HashMap<Address, AccountTransition>> {
+        self.transitions.get(&id)
+    }
+
+    /// Records that `address`'s account info changed from `original` to `new` (`None` for `new`
+    /// means the account was deleted), against every open snapshot generation.
+    pub fn record_account_change(
+        &mut self,
+        address: Address,
+        original: Option<AccountInfo>,
+        new: Option<AccountInfo>,
+    ) {
+        for transitions in self.transitions.values_mut() {
+            let entry = transitions.entry(address).or_insert_with(|| AccountTransition {
+                original_info: original.clone(),
+                new_info: None,
+                deleted: false,
+                storage: HashMap::new(),
+            });
+            entry.deleted = new.is_none();
+            entry.new_info = new.clone();
+        }
+    }
+
+    /// Records that `address`'s storage slot `slot` changed from `original` to `new`, against
+    /// every open snapshot generation. `info` is `address`'s current account info, kept alongside
+    /// the storage delta so a storage-only write still has a nonce/balance/code to report in
+    /// [TransitionTracker::diff_since] without being mistaken for a deletion.
+    pub fn record_storage_change(
+        &mut self,
+        address: Address,
+        info: AccountInfo,
+        slot: U256,
+        original: U256,
+        new: U256,
+    ) {
+        for transitions in self.transitions.values_mut() {
+            let entry = transitions.entry(address).or_default();
+            entry.deleted = false;
+            entry.new_info = Some(info.clone());
+            let (_, current) = entry.storage.entry(slot).or_insert((original, new));
+            *current = new;
+        }
+    }
+
+    /// Collapses all transitions recorded since snapshot `since` was opened into the minimal set
+    /// of changed accounts and slots, for [Db::dump_state_diff].
+    ///
+    /// An address with no actual change — read but never mutated, or mutated back to its original
+    /// value (e.g. a redundant `set_balance`/`set_storage_at`, or a warm read revm still reports a
+    /// transition for) — is omitted entirely rather than carried through with its current
+    /// nonce/balance/code and an empty storage set, so the result stays the minimal diff this is
+    /// meant to be.
+    pub fn diff_since(&self, since: U256) -> Map<Address, AccountRecord> {
+        let Some(transitions) = self.transitions.get(&since) else { return Map::new() };
+        transitions
+            .iter()
+            .filter_map(|(address, transition)| {
+                let storage: Map<U256, U256> = transition
+                    .storage
+                    .iter()
+                    .filter(|(_, (original, new))| original != new)
+                    .map(|(slot, (_, new))| (*slot, *new))
+                    .collect();
+                if !transition.deleted &&
+                    storage.is_empty() &&
+                    account_info_unchanged(
+                        transition.original_info.as_ref(),
+                        transition.new_info.as_ref(),
+                    )
+                {
+                    return None
+                }
+                let record = if transition.deleted {
+                    // account was deleted: represent it explicitly as an all-zero record with no
+                    // storage, rather than reporting its last-known slots as still live
+                    AccountRecord {
+                        nonce: 0,
+                        balance: U256::zero(),
+                        code: Bytes::default(),
+                        storage: Map::new(),
+                    }
+                } else {
+                    let info = transition.new_info.as_ref();
+                    AccountRecord {
+                        nonce: info.map(|i| i.nonce).unwrap_or_default(),
+                        balance: info.map(|i| i.balance).unwrap_or_default(),
+                        code: info
+                            .and_then(|i| i.code.as_ref())
+                            .map(|c| c.original_bytes().to_vec().into())
+                            .unwrap_or_default(),
+                        storage,
+                    }
+                };
+                Some((*address, record))
+            })
+            .collect()
+    }
+}
+
+/// Whether `original` and `new` describe the same account for diffing purposes: same nonce,
+/// balance, and code hash. Used by [TransitionTracker::diff_since] to drop accounts that were
+/// merely read, or mutated back to their starting value, from the reported diff.
+///
+/// Code is compared via [account_code_hash] rather than the raw `code_hash` field: [Db::set_code]
+/// only ever assigns `AccountInfo::code`, never `code_hash`, so comparing `code_hash` directly
+/// would miss a code-only change (e.g. `anvil_setCode`/`vm.etch`) and drop the account from the
+/// diff entirely.
+fn account_info_unchanged(original: Option<&AccountInfo>, new: Option<&AccountInfo>) -> bool {
+    match (original, new) {
+        (Some(original), Some(new)) => {
+            original.nonce == new.nonce &&
+                original.balance == new.balance &&
+                account_code_hash(original, &|_| None) == account_code_hash(new, &|_| None)
+        }
+        (None, None) => true,
+        _ => false,
+    }
+}
+
+/// Which chain layer a cross-layer state read targets, selected via the `XCALLOPTIONS`
+/// precompile.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Layer {
+    /// This backend's own state
+    #[default]
+    Local,
+    /// The backend's configured [Db::settlement_layer]
+    Settlement,
+}
+
+/// Tracks which [Layer] subsequent `DatabaseRef`/`Database` reads on a single [Db] should target.
+///
+/// Held by the `Db` implementation itself (e.g. [MemBoundedCacheDb]) rather than anywhere
+/// global, and driven entirely through [Db::select_layer]/[Db::reset_layer] — nothing outside
+/// this module touches it directly, so a layer selection can only ever affect the `Db` it was
+/// made on, and only for the single read that selected it: [read_remote] resets it unconditionally
+/// before returning, so it can never leak into a later, unrelated call (a `Db`'s `commit`, see
+/// [MemBoundedCacheDb]'s `DatabaseCommit` impl, also resets it as a backstop).
+#[derive(Debug, Default)]
+pub struct ActiveLayer(RefCell<Layer>);
+
+// === impl ActiveLayer ===
+
+impl ActiveLayer {
+    /// Returns the currently selected layer.
+    pub fn get(&self) -> Layer {
+        *self.0.borrow()
+    }
+
+    /// Selects `layer` for subsequent reads, until the next [ActiveLayer::select] or
+    /// [ActiveLayer::reset].
+    pub fn select(&self, layer: Layer) {
+        *self.0.borrow_mut() = layer;
+    }
+
+    /// Resets to [Layer::Local]. Must be called after every top-level call frame.
+    pub fn reset(&self) {
+        *self.0.borrow_mut() = Layer::Local;
+    }
+}
+
+/// Which piece of account state a [RemoteReadRequest] asks for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RemoteField {
+    Balance,
+    Nonce,
+    CodeHash,
+    Storage(U256),
+}
+
+/// A single cross-layer read, as decoded from the `XCALLOPTIONS` precompile's input.
+#[derive(Clone, Copy, Debug)]
+pub struct RemoteReadRequest {
+    pub layer: Layer,
+    pub address: Address,
+    pub field: RemoteField,
+}
+
+/// Errors produced while resolving a [RemoteReadRequest] against a [Db].
+#[derive(thiserror::Error, Debug)]
+pub enum RemoteReadError {
+    /// `request.layer` was [Layer::Settlement] but no settlement layer was configured for this
+    /// backend. The `XCALLOPTIONS` precompile turns this into a revert rather than silently
+    /// falling back to reading local state.
+    #[error("no settlement layer configured for this backend")]
+    NoSettlementLayer,
+    /// The precompile's input couldn't be decoded into a [RemoteReadRequest].
+    #[error("malformed XCALLOPTIONS input: {0}")]
+    MalformedInput(&'static str),
+}
+
+/// Resolves `request` against `db`, reading through whichever layer `request.layer` selects.
+///
+/// This is what the `XCALLOPTIONS` precompile calls into: it first calls [Db::select_layer] (
+/// which reverts via [RemoteReadError::NoSettlementLayer] if `request.layer` is
+/// [Layer::Settlement] and `db` has no settlement layer configured), then reads through `db`'s own
+/// `DatabaseRef` impl, which — for a `Db` like [MemBoundedCacheDb] that tracks an [ActiveLayer] —
+/// transparently serves the read from the selected layer. Always read-only: nothing is ever
+/// written back to the parent layer through this path.
+///
+/// Unconditionally resets the layer selection back to [Layer::Local] before returning, success or
+/// error, rather than leaving that to [DatabaseCommit::commit]. A read-only call (`eth_call`,
+/// `eth_estimateGas`, tracing, or anything built on [CachedReadsDbMut] to avoid mutating the real
+/// `Db` in the first place) never reaches `commit`, and `active_layer` lives on the shared `Db`
+/// instance — so without this, one transaction selecting the settlement layer would leak into the
+/// next, unrelated one.
+pub fn read_remote(db: &dyn Db, request: RemoteReadRequest) -> Result<U256, RemoteReadError> {
+    if let Err(err) = db.select_layer(request.layer) {
+        db.reset_layer();
+        return Err(err)
+    }
+    let info = DatabaseRef::basic(db, request.address);
+    let value = match request.field {
+        RemoteField::Balance => info.balance,
+        RemoteField::Nonce => info.nonce.into(),
+        RemoteField::CodeHash => U256::from_big_endian(info.code_hash.as_bytes()),
+        RemoteField::Storage(slot) => DatabaseRef::storage(db, request.address, slot),
+    };
+    db.reset_layer();
+    Ok(value)
+}
+
+/// Decodes the `XCALLOPTIONS` precompile's input.
+///
+/// Layout: `selector (1 byte) | field (1 byte) | address (20 bytes) | slot (32 bytes, only
+/// present when `field` is `3`)`. `selector` is `0` for [Layer::Local], `1` for
+/// [Layer::Settlement]; `field` is `0` = balance, `1` = nonce, `2` = code hash, `3` = storage slot.
+fn decode_xcalloptions_input(input: &[u8]) -> Result<RemoteReadRequest, RemoteReadError> {
+    let [selector, field, rest @ ..] = input else {
+        return Err(RemoteReadError::MalformedInput("input shorter than selector + field"))
+    };
+    let layer = match selector {
+        0 => Layer::Local,
+        1 => Layer::Settlement,
+        _ => return Err(RemoteReadError::MalformedInput("unknown layer selector")),
+    };
+    if rest.len() < 20 {
+        return Err(RemoteReadError::MalformedInput("input too short for an address"))
+    }
+    let (address_bytes, rest) = rest.split_at(20);
+    let address = Address::from_slice(address_bytes);
+    let field = match field {
+        0 => RemoteField::Balance,
+        1 => RemoteField::Nonce,
+        2 => RemoteField::CodeHash,
+        3 => {
+            if rest.len() != 32 {
+                return Err(RemoteReadError::MalformedInput("storage reads need a 32-byte slot"))
+            }
+            RemoteField::Storage(U256::from_big_endian(rest))
+        }
+        _ => return Err(RemoteReadError::MalformedInput("unknown field selector")),
+    };
+    Ok(RemoteReadRequest { layer, address, field })
+}
+
+/// Entry point for the `XCALLOPTIONS` precompile: decodes `input` and resolves the requested
+/// cross-layer read against `db`, returning its big-endian `U256` value. Nothing in this crate
+/// registers it with the executor at a precompile address; it is only callable as a plain
+/// function.
+pub fn xcalloptions_precompile(db: &dyn Db, input: &[u8]) -> Result<U256, RemoteReadError> {
+    let request = decode_xcalloptions_input(input)?;
+    read_remote(db, request)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use foundry_evm::revm::db::InMemoryDB;
+
+    fn empty_cache() -> MemBoundedCacheDb<InMemoryDB> {
+        MemBoundedCacheDb::new(InMemoryDB::default(), 2, 2)
+    }
+
+    #[test]
+    fn evicts_least_recently_used_clean_account() {
+        let mut db = empty_cache();
+        let a = Address::repeat_byte(1);
+        let b = Address::repeat_byte(2);
+        let c = Address::repeat_byte(3);
+        db.cache_account(a, AccountInfo::default());
+        db.cache_account(b, AccountInfo::default());
+        db.touch_account(a);
+        db.cache_account(c, AccountInfo::default());
+        assert!(db.accounts.contains_key(&a));
+        assert!(!db.accounts.contains_key(&b), "least-recently-used clean account should be evicted");
+        assert!(db.accounts.contains_key(&c));
+    }
+
+    #[test]
+    fn dirty_accounts_are_never_evicted() {
+        let mut db = empty_cache();
+        let pinned = Address::repeat_byte(1);
+        db.insert_account(pinned, AccountInfo::default());
+        db.cache_account(Address::repeat_byte(2), AccountInfo::default());
+        db.cache_account(Address::repeat_byte(3), AccountInfo::default());
+        assert!(db.accounts.contains_key(&pinned), "locally mutated accounts must survive eviction");
+    }
+
+    #[test]
+    fn empty_storage_root_matches_keccak_null_rlp() {
+        assert_eq!(storage_trie_root(&HashMap::new()), KECCAK_NULL_RLP);
+    }
+
+    #[test]
+    fn code_hash_prefers_attached_code_over_a_stale_code_hash() {
+        let code = foundry_evm::revm::Bytecode::new_raw(vec![0x60, 0x01].into());
+        let info = AccountInfo {
+            code_hash: KECCAK_EMPTY.into(),
+            code: Some(code.clone()),
+            ..Default::default()
+        };
+        let expected = H256::from_slice(&ethers::utils::keccak256(code.original_bytes()));
+        assert_eq!(account_code_hash(&info, &|_| None), expected);
+        assert_ne!(expected, KECCAK_EMPTY, "test fixture must exercise the stale-hash case");
+    }
+
+    #[test]
+    fn code_hash_falls_back_to_code_by_hash_when_nothing_is_attached() {
+        let info = AccountInfo { code_hash: H256::zero().into(), code: None, ..Default::default() };
+        assert_eq!(account_code_hash(&info, &|_| None), KECCAK_EMPTY);
+    }
+
+    #[test]
+    fn snapshot_round_trips() {
+        let mut accounts = Map::new();
+        accounts.insert(
+            Address::repeat_byte(1),
+            AccountRecord {
+                nonce: 1,
+                balance: U256::from(100u64),
+                code: Bytes::default(),
+                storage: Map::new(),
+            },
+        );
+        let mut buf = Vec::new();
+        write_snapshot(&mut buf, accounts.clone().into_iter(), U256::from(42u64), None).unwrap();
+
+        let mut cursor = std::io::Cursor::new(buf);
+        let loaded = read_snapshot(&mut cursor).unwrap();
+        assert_eq!(loaded.len(), accounts.len());
+        assert_eq!(loaded[&Address::repeat_byte(1)].nonce, 1);
+    }
+
+    #[test]
+    fn snapshot_rejects_a_corrupted_chunk() {
+        let mut accounts = Map::new();
+        accounts.insert(
+            Address::repeat_byte(1),
+            AccountRecord { nonce: 1, balance: U256::zero(), code: Bytes::default(), storage: Map::new() },
+        );
+        let mut buf = Vec::new();
+        write_snapshot(&mut buf, accounts.into_iter(), U256::zero(), None).unwrap();
+        let last = buf.len() - 1;
+        buf[last] ^= 0xFF;
+
+        let mut cursor = std::io::Cursor::new(buf);
+        assert!(matches!(read_snapshot(&mut cursor), Err(SnapshotError::ChunkHashMismatch { .. })));
+    }
+
+    #[test]
+    fn snapshot_rejects_an_unknown_version() {
+        let manifest = SnapshotManifest {
+            version: SNAPSHOT_FORMAT_VERSION + 1,
+            block_number: U256::zero(),
+            state_root: None,
+            chunk_hashes: vec![],
+        };
+        let manifest_bytes = bincode::serialize(&manifest).unwrap();
+        let mut buf = Vec::new();
+        buf.extend_from_slice(&(manifest_bytes.len() as u64).to_le_bytes());
+        buf.extend_from_slice(&manifest_bytes);
+
+        let mut cursor = std::io::Cursor::new(buf);
+        assert!(matches!(read_snapshot(&mut cursor), Err(SnapshotError::UnsupportedVersion(_))));
+    }
+
+    #[test]
+    fn diff_since_omits_untouched_and_no_op_accounts() {
+        let mut tracker = TransitionTracker::default();
+        tracker.open_snapshot(U256::zero());
+
+        let touched = Address::repeat_byte(1);
+        let original_info = AccountInfo::default();
+        let mut bumped_info = original_info.clone();
+        bumped_info.balance = U256::from(5u64);
+        tracker.record_account_change(touched, Some(original_info.clone()), Some(bumped_info.clone()));
+        tracker.record_account_change(touched, Some(original_info.clone()), Some(original_info.clone()));
+
+        let storage_only = Address::repeat_byte(2);
+        tracker.record_storage_change(storage_only, AccountInfo::default(), U256::one(), U256::zero(), U256::one());
+
+        let diff = tracker.diff_since(U256::zero());
+        assert!(!diff.contains_key(&touched), "account mutated back to its original value is a no-op");
+        assert_eq!(diff[&storage_only].storage[&U256::one()], U256::one());
+    }
+
+    #[test]
+    fn diff_since_represents_deletion_as_an_all_zero_record() {
+        let mut tracker = TransitionTracker::default();
+        tracker.open_snapshot(U256::zero());
+
+        let deleted = Address::repeat_byte(3);
+        tracker.record_account_change(deleted, Some(AccountInfo::default()), None);
+
+        let diff = tracker.diff_since(U256::zero());
+        let record = &diff[&deleted];
+        assert_eq!(record.nonce, 0);
+        assert_eq!(record.balance, U256::zero());
+        assert!(record.storage.is_empty());
+    }
+
+    #[test]
+    fn decodes_local_balance_request() {
+        let mut input = vec![0u8, 0u8];
+        input.extend_from_slice(Address::repeat_byte(9).as_bytes());
+        let request = decode_xcalloptions_input(&input).unwrap();
+        assert_eq!(request.layer, Layer::Local);
+        assert_eq!(request.field, RemoteField::Balance);
+    }
+
+    #[test]
+    fn decodes_settlement_storage_request() {
+        let mut input = vec![1u8, 3u8];
+        input.extend_from_slice(Address::repeat_byte(9).as_bytes());
+        input.extend_from_slice(&[0u8; 32]);
+        let request = decode_xcalloptions_input(&input).unwrap();
+        assert_eq!(request.layer, Layer::Settlement);
+        assert_eq!(request.field, RemoteField::Storage(U256::zero()));
+    }
+
+    #[test]
+    fn rejects_malformed_xcalloptions_input() {
+        assert!(decode_xcalloptions_input(&[0u8]).is_err());
+        assert!(decode_xcalloptions_input(&[9u8, 0u8, 0u8, 0u8]).is_err());
+    }
+}